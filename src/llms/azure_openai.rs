@@ -0,0 +1,126 @@
+use anyhow::{anyhow, bail, Ok, Result};
+use std::fmt;
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+
+use tiktoken_rs::async_openai::get_chat_completion_max_tokens;
+
+use crate::{settings::AzureOpenAISettings, util::build_http_client};
+use async_openai::{
+    config::AzureConfig,
+    types::{ChatCompletionRequestMessageArgs, CreateChatCompletionRequestArgs, Role},
+    Client,
+};
+
+use super::llm_client::LlmClient;
+use super::openai::OpenAIClient;
+
+const COMPLETION_TOKEN_LIMIT: usize = 100;
+
+pub(crate) struct AzureOpenAIClient {
+    model: String,
+    client: Client<AzureConfig>,
+}
+
+impl Debug for AzureOpenAIClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AzureOpenAIClient")
+            .field("model", &self.model)
+            .finish()
+    }
+}
+
+impl AzureOpenAIClient {
+    pub(crate) fn new(settings: AzureOpenAISettings) -> Result<Self, anyhow::Error> {
+        let api_base = settings.api_base.unwrap_or_default();
+        if api_base.is_empty() {
+            bail!("No Azure OpenAI endpoint found. Please provide a valid `api_base`.");
+        }
+        let api_key = settings.api_key.unwrap_or_default();
+        if api_key.is_empty() {
+            bail!("No Azure OpenAI API key found. Please provide a valid API key.");
+        }
+        let deployment_id = settings.deployment_id.unwrap_or_default();
+        if deployment_id.is_empty() {
+            bail!("No Azure OpenAI deployment id found. Please provide a valid `deployment_id`.");
+        }
+        let api_version = settings.api_version.unwrap_or_default();
+        if api_version.is_empty() {
+            bail!("No Azure OpenAI api version found. Please provide a valid `api_version`.");
+        }
+        let model = settings.model.unwrap_or_default();
+        if model.is_empty() {
+            bail!("No Azure OpenAI model configured. Please choose a valid model to use.");
+        }
+
+        let azure_config = AzureConfig::new()
+            .with_api_base(&api_base)
+            .with_api_key(api_key)
+            .with_deployment_id(deployment_id)
+            .with_api_version(api_version);
+
+        let http_client = build_http_client(&settings.extra, false)?;
+        let azure_client =
+            Client::<AzureConfig>::with_config(azure_config).with_http_client(http_client);
+
+        Ok(Self {
+            model,
+            client: azure_client,
+        })
+    }
+
+    pub(crate) async fn get_chat_completions(&self, prompt: &str) -> Result<String> {
+        let messages = [ChatCompletionRequestMessageArgs::default()
+            .role(Role::User)
+            .content(prompt)
+            .build()?];
+        let prompt_token_limit = get_chat_completion_max_tokens(&self.model, &messages)?;
+
+        if prompt_token_limit < COMPLETION_TOKEN_LIMIT {
+            let error_msg =
+                "skipping... diff is too large for the model. Consider using a model with a larger context window.".to_string();
+            warn!("{}", error_msg);
+            bail!(error_msg)
+        }
+
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(&self.model)
+            .messages(messages)
+            .build()?;
+
+        let response = self.client.chat().create(request).await?;
+
+        if let Some(choice) = response.choices.into_iter().next() {
+            debug!(
+                "{}: Role: {}  Content: {}",
+                choice.index,
+                choice.message.role,
+                choice.message.content.clone().unwrap_or_default()
+            );
+
+            return choice
+                .message
+                .content
+                .ok_or(anyhow!("No completion results returned from Azure OpenAI."));
+        }
+
+        bail!("No completion results returned from Azure OpenAI.")
+    }
+}
+
+#[async_trait]
+impl LlmClient for AzureOpenAIClient {
+    /// Sends a request to Azure OpenAI's API to get a text completion.
+    /// It takes a prompt as input, and returns the completion.
+    async fn completions(&self, prompt: &str) -> Result<String> {
+        if !OpenAIClient::should_use_chat_completion(&self.model) {
+            bail!(
+                "Azure OpenAI deployment \"{}\" does not support the chat completions API. Please deploy a gpt-3.5-turbo/gpt-4 model.",
+                self.model
+            );
+        }
+        let completion = self.get_chat_completions(prompt).await?;
+        Ok(completion.trim().to_string())
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt::Debug;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Common interface implemented by every supported LLM backend (OpenAI,
+/// Azure OpenAI, local/Ollama-compatible servers, ...).
+#[async_trait]
+pub(crate) trait LlmClient: Debug + Send + Sync {
+    /// Sends `prompt` to the backend and returns the generated completion.
+    async fn completions(&self, prompt: &str) -> Result<String>;
+
+    /// Streaming variant of [`Self::completions`]. `handler` is called with
+    /// each delta chunk as it arrives, so a caller (e.g. the CLI) can render
+    /// it token-by-token; the full completion is still returned for callers
+    /// that only care about the final string (git hooks, tests).
+    ///
+    /// Backends without native streaming support can rely on this default,
+    /// which just calls `handler` once with the full response.
+    async fn completions_streaming(
+        &self,
+        prompt: &str,
+        handler: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        let completion = self.completions(prompt).await?;
+        handler(&completion);
+        Ok(completion)
+    }
+}
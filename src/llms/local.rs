@@ -0,0 +1,150 @@
+use anyhow::{anyhow, bail, Ok, Result};
+use std::fmt;
+use std::fmt::Debug;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::{settings::LocalSettings, util::build_http_client};
+
+use super::llm_client::LlmClient;
+
+const COMPLETION_TOKEN_LIMIT: usize = 100;
+/// Context window assumed for a model that isn't listed in `max_tokens`.
+const DEFAULT_MAX_TOKENS: usize = 4096;
+/// Rough chars-per-token ratio used when the exact tokenizer is unknown.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Targets a self-hosted, OpenAI-compatible chat endpoint (Ollama, LocalAI,
+/// vLLM, ...) that doesn't require an API key.
+pub(crate) struct LocalClient {
+    model: String,
+    chat_endpoint: String,
+    auth_header: Option<String>,
+    max_tokens: usize,
+    http_client: reqwest::Client,
+}
+
+impl Debug for LocalClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalClient")
+            .field("model", &self.model)
+            .field("chat_endpoint", &self.chat_endpoint)
+            .finish()
+    }
+}
+
+impl LocalClient {
+    pub(crate) fn new(settings: LocalSettings) -> Result<Self, anyhow::Error> {
+        let chat_endpoint = settings.chat_endpoint.unwrap_or_default();
+        if chat_endpoint.is_empty() {
+            bail!("No chat endpoint configured. Please provide a valid `chat_endpoint`.");
+        }
+        let model = settings.model.unwrap_or_default();
+        if model.is_empty() {
+            bail!("No model configured. Please choose a valid model to use.");
+        }
+        let max_tokens = settings
+            .max_tokens
+            .get(&model)
+            .copied()
+            .unwrap_or(DEFAULT_MAX_TOKENS);
+
+        let http_client = build_http_client(&settings.extra, false)?;
+
+        Ok(Self {
+            model,
+            chat_endpoint,
+            auth_header: settings.auth_header,
+            max_tokens,
+            http_client,
+        })
+    }
+
+    /// Budget left for the completion, sized from the per-model `max_tokens`
+    /// setting instead of `tiktoken_rs`, which doesn't know local model names.
+    fn prompt_token_limit(&self, prompt: &str) -> usize {
+        let estimated_prompt_tokens = prompt.len() / CHARS_PER_TOKEN;
+        self.max_tokens.saturating_sub(estimated_prompt_tokens)
+    }
+}
+
+#[async_trait]
+impl LlmClient for LocalClient {
+    /// Sends a request to the configured local chat endpoint to get a text
+    /// completion. It takes a prompt as input, and returns the completion.
+    async fn completions(&self, prompt: &str) -> Result<String> {
+        if self.prompt_token_limit(prompt) < COMPLETION_TOKEN_LIMIT {
+            let error_msg =
+                "Skipping... The diff is too large for the current model. Consider using a model with a larger context window.".to_string();
+            warn!("{}", error_msg);
+            bail!(error_msg)
+        }
+
+        let body = json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+            "stream": false,
+        });
+
+        let mut request = self.http_client.post(&self.chat_endpoint).json(&body);
+        if let Some(auth_header) = &self.auth_header {
+            request = request.header(reqwest::header::AUTHORIZATION, auth_header);
+        }
+
+        debug!("Sending request to {}:\n{:?}", self.chat_endpoint, body);
+
+        let response = request.send().await?.error_for_status()?;
+        let response: serde_json::Value = response.json().await?;
+
+        let completion = response
+            .get("choices")
+            .and_then(|choices| choices.get(0))
+            .and_then(|choice| choice.get("message"))
+            .and_then(|message| message.get("content"))
+            .or_else(|| {
+                response
+                    .get("message")
+                    .and_then(|message| message.get("content"))
+            })
+            .and_then(|content| content.as_str())
+            .ok_or_else(|| {
+                anyhow!(
+                    "No completion results returned from {}.",
+                    self.chat_endpoint
+                )
+            })?;
+
+        Ok(completion.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_with_max_tokens(max_tokens: usize) -> LocalClient {
+        LocalClient {
+            model: "test-model".to_string(),
+            chat_endpoint: "http://localhost/v1/chat/completions".to_string(),
+            auth_header: None,
+            max_tokens,
+            http_client: reqwest::Client::new(),
+        }
+    }
+
+    #[test]
+    fn prompt_token_limit_subtracts_the_estimated_prompt_tokens() {
+        let client = client_with_max_tokens(100);
+        // 40 chars / 4 chars-per-token ~= 10 estimated tokens.
+        let prompt = "x".repeat(40);
+        assert_eq!(client.prompt_token_limit(&prompt), 90);
+    }
+
+    #[test]
+    fn prompt_token_limit_saturates_at_zero_for_oversized_prompts() {
+        let client = client_with_max_tokens(10);
+        let prompt = "x".repeat(1000);
+        assert_eq!(client.prompt_token_limit(&prompt), 0);
+    }
+}
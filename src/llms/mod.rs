@@ -0,0 +1,42 @@
+pub(crate) mod llm_client;
+
+pub(crate) use llm_client::LlmClient;
+
+/// Declares one `mod` per backend, wires its settings into [`ClientConfig`]
+/// and generates [`create_client`] to turn a selected config into a boxed
+/// [`LlmClient`]. Adding a new provider only means adding one line here plus
+/// the settings struct it needs.
+macro_rules! register_clients {
+    (
+        $(($module:ident, $name:literal, $settings:ident, $client:ident),)+
+    ) => {
+        $(mod $module;)+
+        $(pub(crate) use $module::$client;)+
+
+        #[derive(Debug, Clone, serde::Deserialize)]
+        #[serde(tag = "type", rename_all = "snake_case")]
+        pub(crate) enum ClientConfig {
+            $(
+                #[serde(rename = $name)]
+                $client(crate::settings::$settings),
+            )+
+        }
+
+        pub(crate) fn create_client(config: ClientConfig) -> anyhow::Result<Box<dyn LlmClient>> {
+            match config {
+                $(ClientConfig::$client(settings) => Ok(Box::new($client::new(settings)?)),)+
+            }
+        }
+    };
+}
+
+register_clients!(
+    (openai, "openai", OpenAISettings, OpenAIClient),
+    (
+        azure_openai,
+        "azure",
+        AzureOpenAISettings,
+        AzureOpenAIClient
+    ),
+    (local, "local", LocalSettings, LocalClient),
+);
@@ -1,14 +1,13 @@
 use anyhow::{anyhow, bail, Ok, Result};
 use std::fmt;
 use std::fmt::Debug;
-use std::time::Duration;
 
 use async_trait::async_trait;
+use futures::StreamExt;
 
-use reqwest::{tls, Proxy};
 use tiktoken_rs::{async_openai::get_chat_completion_max_tokens, get_completion_max_tokens};
 
-use crate::{settings::OpenAISettings, util::HTTP_USER_AGENT};
+use crate::{settings::OpenAISettings, util::build_http_client};
 use async_openai::{
     config::OpenAIConfig,
     types::{
@@ -21,15 +20,34 @@ use async_openai::{
 use super::llm_client::LlmClient;
 const COMPLETION_TOKEN_LIMIT: usize = 100;
 
+/// A model picked by [`OpenAIClient::select_model`], together with the
+/// request path it has to be sent through and, for the legacy completions
+/// path, the remaining prompt budget.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum SelectedModel {
+    Completion {
+        model: String,
+        prompt_token_limit: usize,
+    },
+    ChatCompletion {
+        model: String,
+    },
+}
+
 pub(crate) struct OpenAIClient {
-    model: String,
+    /// Ordered model chain: the configured model first, then any fallbacks
+    /// to retry with a larger context window when the diff doesn't fit.
+    models: Vec<String>,
+    /// Whether `completions` should stream the response internally rather
+    /// than issue a single buffered request.
+    stream: bool,
     client: Client<OpenAIConfig>,
 }
 
 impl Debug for OpenAIClient {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("OpenAIClient")
-            .field("model", &self.model)
+            .field("models", &self.models)
             .finish()
     }
 }
@@ -49,35 +67,17 @@ impl OpenAIClient {
                 .with_api_key(api_key)
         };
         let mut openai_client = Client::<OpenAIConfig>::with_config(openai_config);
-        // TODO make configurable
-        let mut http_client = reqwest::Client::builder()
-            .gzip(true)
-            .brotli(true)
-            .timeout(Duration::from_secs(60))
-            .user_agent(HTTP_USER_AGENT);
-
-        if api_base.is_empty() {
-            // Optimized HTTP client
-            http_client = http_client
-                .http2_prior_knowledge()
-                .https_only(true)
-                .http2_adaptive_window(true)
-                .tcp_keepalive(Duration::from_secs(60))
-                .http2_keep_alive_interval(Duration::from_secs(60))
-                .http2_keep_alive_while_idle(true)
-                .min_tls_version(tls::Version::TLS_1_2);
-        }
+
         let model = settings.model.unwrap_or_default();
         if api_base.is_empty() && model.is_empty() {
             bail!("No OpenAI model configured. Please choose a valid model to use.");
         }
+        let mut models = vec![model];
+        models.extend(settings.model_fallback);
+        let stream = settings.stream.unwrap_or(false);
 
-        if let Some(proxy) = settings.proxy {
-            if !proxy.is_empty() {
-                http_client = http_client.proxy(Proxy::all(proxy)?);
-            }
-        }
-        openai_client = openai_client.with_http_client(http_client.build()?);
+        let http_client = build_http_client(&settings.extra, api_base.is_empty())?;
+        openai_client = openai_client.with_http_client(http_client);
 
         if settings.retries.unwrap_or_default() > 0 {
             let backoff = backoff::ExponentialBackoffBuilder::new()
@@ -86,7 +86,8 @@ impl OpenAIClient {
             openai_client = openai_client.with_backoff(backoff);
         }
         Ok(Self {
-            model,
+            models,
+            stream,
             client: openai_client,
         })
     }
@@ -96,18 +97,48 @@ impl OpenAIClient {
             || model.to_lowercase().starts_with("gpt-3.5-turbo")
     }
 
-    pub(crate) async fn get_completions(&self, prompt: &str) -> Result<String> {
-        let prompt_token_limit = get_completion_max_tokens(&self.model, prompt)?;
-
-        if prompt_token_limit < COMPLETION_TOKEN_LIMIT {
-            let error_msg =
-"Skipping... The diff is too large for the current model. Consider using a model with a larger context window.".to_string();
-            warn!("{}", error_msg);
-            bail!(error_msg)
+    /// Walks the configured model chain and returns the first one whose
+    /// remaining budget covers `prompt`, re-running the token check and the
+    /// chat-vs-completion decision for each candidate in turn.
+    fn select_model(&self, prompt: &str) -> Result<SelectedModel> {
+        for model in &self.models {
+            if OpenAIClient::should_use_chat_completion(model) {
+                let messages = [ChatCompletionRequestMessageArgs::default()
+                    .role(Role::User)
+                    .content(prompt)
+                    .build()?];
+                let prompt_token_limit = get_chat_completion_max_tokens(model, &messages)?;
+                if prompt_token_limit >= COMPLETION_TOKEN_LIMIT {
+                    return Ok(SelectedModel::ChatCompletion {
+                        model: model.clone(),
+                    });
+                }
+            } else {
+                let prompt_token_limit = get_completion_max_tokens(model, prompt)?;
+                if prompt_token_limit >= COMPLETION_TOKEN_LIMIT {
+                    return Ok(SelectedModel::Completion {
+                        model: model.clone(),
+                        prompt_token_limit,
+                    });
+                }
+            }
         }
+
+        let error_msg =
+            "Skipping... The diff is too large for every configured model. Consider adding a model with a larger context window to `model_fallback`.".to_string();
+        warn!("{}", error_msg);
+        bail!(error_msg)
+    }
+
+    pub(crate) async fn get_completions(
+        &self,
+        model: &str,
+        prompt: &str,
+        prompt_token_limit: usize,
+    ) -> Result<String> {
         // Create request using builder pattern
         let request = CreateCompletionRequestArgs::default()
-            .model(&self.model)
+            .model(model)
             .prompt(prompt)
             .max_tokens(prompt_token_limit as u16)
             .temperature(0.5)
@@ -133,22 +164,14 @@ impl OpenAIClient {
         completion
     }
 
-    pub(crate) async fn get_chat_completions(&self, prompt: &str) -> Result<String> {
+    pub(crate) async fn get_chat_completions(&self, model: &str, prompt: &str) -> Result<String> {
         let messages = [ChatCompletionRequestMessageArgs::default()
             .role(Role::User)
             .content(prompt)
             .build()?];
-        let prompt_token_limit = get_chat_completion_max_tokens(&self.model, &messages)?;
-
-        if prompt_token_limit < COMPLETION_TOKEN_LIMIT {
-            let error_msg =
-                "skipping... diff is too large for the model. Consider using a model with a larger context window.".to_string();
-            warn!("{}", error_msg);
-            bail!(error_msg)
-        }
 
         let request = CreateChatCompletionRequestArgs::default()
-            .model(&self.model)
+            .model(model)
             .messages(messages)
             .build()?;
 
@@ -170,18 +193,137 @@ impl OpenAIClient {
 
         bail!("No completion results returned from OpenAI.")
     }
+
+    pub(crate) async fn get_chat_completions_streaming(
+        &self,
+        model: &str,
+        prompt: &str,
+        handler: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        let messages = [ChatCompletionRequestMessageArgs::default()
+            .role(Role::User)
+            .content(prompt)
+            .build()?];
+
+        let request = CreateChatCompletionRequestArgs::default()
+            .model(model)
+            .messages(messages)
+            .build()?;
+
+        let mut stream = self.client.chat().create_stream(request).await?;
+        let mut completion = String::new();
+
+        while let Some(response) = stream.next().await {
+            let response = response?;
+            if let Some(choice) = response.choices.first() {
+                if let Some(content) = &choice.delta.content {
+                    handler(content);
+                    completion.push_str(content);
+                }
+            }
+        }
+
+        if completion.is_empty() {
+            bail!("No completion results returned from OpenAI.")
+        }
+
+        Ok(completion)
+    }
 }
 
 #[async_trait]
 impl LlmClient for OpenAIClient {
     /// Sends a request to OpenAI's API to get a text completion.
     /// It takes a prompt as input, and returns the completion.
+    ///
+    /// When `settings.stream` is enabled, this is served internally through
+    /// [`Self::completions_streaming`] with a no-op handler, so the request
+    /// still goes out over the streaming endpoint even though the caller
+    /// only wants the final string.
     async fn completions(&self, prompt: &str) -> Result<String> {
-        let completion = if OpenAIClient::should_use_chat_completion(&self.model) {
-            self.get_chat_completions(prompt).await?
-        } else {
-            self.get_completions(prompt).await?
+        if self.stream {
+            return self.completions_streaming(prompt, &mut |_| {}).await;
+        }
+
+        let completion = match self.select_model(prompt)? {
+            SelectedModel::ChatCompletion { model } => {
+                self.get_chat_completions(&model, prompt).await?
+            }
+            SelectedModel::Completion {
+                model,
+                prompt_token_limit,
+            } => {
+                self.get_completions(&model, prompt, prompt_token_limit)
+                    .await?
+            }
+        };
+        Ok(completion.trim().to_string())
+    }
+
+    /// Streams chat-completion models token-by-token via async-openai's SSE
+    /// stream. The legacy, non-chat completions endpoint doesn't support
+    /// streaming, so models on that path fall back to a single buffered call.
+    async fn completions_streaming(
+        &self,
+        prompt: &str,
+        handler: &mut dyn FnMut(&str),
+    ) -> Result<String> {
+        let completion = match self.select_model(prompt)? {
+            SelectedModel::ChatCompletion { model } => {
+                self.get_chat_completions_streaming(&model, prompt, handler)
+                    .await?
+            }
+            SelectedModel::Completion {
+                model,
+                prompt_token_limit,
+            } => {
+                let completion = self
+                    .get_completions(&model, prompt, prompt_token_limit)
+                    .await?;
+                handler(&completion);
+                completion
+            }
         };
         Ok(completion.trim().to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client_with_models(models: Vec<&str>) -> OpenAIClient {
+        OpenAIClient {
+            models: models.into_iter().map(String::from).collect(),
+            stream: false,
+            client: Client::<OpenAIConfig>::with_config(OpenAIConfig::new().with_api_key("test")),
+        }
+    }
+
+    #[test]
+    fn select_model_picks_the_first_model_that_fits() {
+        let client = client_with_models(vec!["gpt-3.5-turbo", "gpt-4"]);
+        let selected = client.select_model("hello world").unwrap();
+        assert_eq!(
+            selected,
+            SelectedModel::ChatCompletion {
+                model: "gpt-3.5-turbo".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn select_model_uses_the_legacy_completions_path_for_non_chat_models() {
+        let client = client_with_models(vec!["text-davinci-003"]);
+        match client.select_model("hello world").unwrap() {
+            SelectedModel::Completion { model, .. } => assert_eq!(model, "text-davinci-003"),
+            other => panic!("expected the legacy completions path, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn select_model_gives_up_when_no_model_is_configured() {
+        let client = client_with_models(vec![]);
+        assert!(client.select_model("hello world").is_err());
+    }
+}
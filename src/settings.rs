@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// Knobs shared by every [`crate::llms::llm_client::LlmClient`] backend,
+/// independent of which provider is selected.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub(crate) struct ExtraSettings {
+    pub(crate) proxy: Option<String>,
+    /// Seconds allowed for the TCP/TLS handshake.
+    pub(crate) connect_timeout: Option<u64>,
+    /// Seconds allowed for the whole request, including the response body.
+    pub(crate) request_timeout: Option<u64>,
+    /// Seconds between HTTP/2 keep-alive pings, used for both the TCP
+    /// keepalive and the HTTP/2 ping interval.
+    pub(crate) keep_alive_interval: Option<u64>,
+    /// Minimum TLS version to accept: `"1.0"`, `"1.1"`, `"1.2"` (default) or
+    /// `"1.3"`.
+    pub(crate) min_tls_version: Option<String>,
+    /// Enable the HTTP/2 prior-knowledge path for a custom `api_base`. Only
+    /// set this for endpoints known to support HTTP/2 without negotiation.
+    pub(crate) force_http2: Option<bool>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub(crate) struct OpenAISettings {
+    pub(crate) api_base: Option<String>,
+    pub(crate) api_key: Option<String>,
+    pub(crate) model: Option<String>,
+    pub(crate) retries: Option<u32>,
+    /// Ordered list of models to retry with, largest context window last,
+    /// when the diff doesn't fit in `model`'s.
+    #[serde(default)]
+    pub(crate) model_fallback: Vec<String>,
+    /// Send chat-completion requests over the streaming endpoint. Off by
+    /// default. `completions` still collects the streamed chunks and returns
+    /// the complete string; only `completions_streaming` hands the chunks to
+    /// its caller as they arrive.
+    pub(crate) stream: Option<bool>,
+    #[serde(default)]
+    pub(crate) extra: ExtraSettings,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub(crate) struct AzureOpenAISettings {
+    pub(crate) api_base: Option<String>,
+    pub(crate) api_key: Option<String>,
+    pub(crate) deployment_id: Option<String>,
+    pub(crate) api_version: Option<String>,
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) extra: ExtraSettings,
+}
+
+/// Settings for a self-hosted, OpenAI-compatible chat endpoint
+/// (Ollama, LocalAI, vLLM, ...) that needs no API key.
+#[derive(Debug, Clone, Deserialize, Default)]
+pub(crate) struct LocalSettings {
+    /// Full URL of the chat endpoint, e.g. `http://localhost:11434/api/chat`
+    /// or `http://localhost:8080/v1/chat/completions`.
+    pub(crate) chat_endpoint: Option<String>,
+    pub(crate) model: Option<String>,
+    /// Raw `Authorization` header value, e.g. `"Bearer local-key"`. Most
+    /// local servers don't require one at all.
+    pub(crate) auth_header: Option<String>,
+    /// Context window, in tokens, per model name. `tiktoken_rs` has no idea
+    /// about local model names, so this is how the prompt budget is sized.
+    #[serde(default)]
+    pub(crate) max_tokens: HashMap<String, usize>,
+    #[serde(default)]
+    pub(crate) extra: ExtraSettings,
+}
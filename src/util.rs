@@ -0,0 +1,76 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use reqwest::{tls, Proxy};
+
+use crate::settings::ExtraSettings;
+
+pub(crate) const HTTP_USER_AGENT: &str = concat!("gptcommit/", env!("CARGO_PKG_VERSION"));
+
+/// Build the `reqwest` client shared by every `LlmClient` backend.
+///
+/// `optimized` enables the HTTP/2 prior-knowledge tuning that only makes sense
+/// against endpoints that are known to support it by default (OpenAI itself).
+/// A custom `api_base` can opt into the same tuning via `extra.force_http2`
+/// for self-hosted gateways that support it too.
+pub(crate) fn build_http_client(extra: &ExtraSettings, optimized: bool) -> Result<reqwest::Client> {
+    let mut builder = reqwest::Client::builder()
+        .gzip(true)
+        .brotli(true)
+        .timeout(Duration::from_secs(extra.request_timeout.unwrap_or(60)))
+        .user_agent(HTTP_USER_AGENT);
+
+    if let Some(connect_timeout) = extra.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_secs(connect_timeout));
+    }
+
+    builder = builder.min_tls_version(parse_min_tls_version(extra.min_tls_version.as_deref()));
+
+    if optimized || extra.force_http2.unwrap_or(false) {
+        let keep_alive_interval = Duration::from_secs(extra.keep_alive_interval.unwrap_or(60));
+        builder = builder
+            .http2_prior_knowledge()
+            .https_only(true)
+            .http2_adaptive_window(true)
+            .tcp_keepalive(keep_alive_interval)
+            .http2_keep_alive_interval(keep_alive_interval)
+            .http2_keep_alive_while_idle(true);
+    }
+
+    if let Some(proxy) = &extra.proxy {
+        if !proxy.is_empty() {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Falls back to TLS 1.2 for an unset or unrecognized `min_tls_version`.
+fn parse_min_tls_version(version: Option<&str>) -> tls::Version {
+    match version {
+        Some("1.0") => tls::Version::TLS_1_0,
+        Some("1.1") => tls::Version::TLS_1_1,
+        Some("1.3") => tls::Version::TLS_1_3,
+        _ => tls::Version::TLS_1_2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_min_tls_version_maps_known_versions() {
+        assert_eq!(parse_min_tls_version(Some("1.0")), tls::Version::TLS_1_0);
+        assert_eq!(parse_min_tls_version(Some("1.1")), tls::Version::TLS_1_1);
+        assert_eq!(parse_min_tls_version(Some("1.2")), tls::Version::TLS_1_2);
+        assert_eq!(parse_min_tls_version(Some("1.3")), tls::Version::TLS_1_3);
+    }
+
+    #[test]
+    fn parse_min_tls_version_falls_back_to_tls_1_2() {
+        assert_eq!(parse_min_tls_version(None), tls::Version::TLS_1_2);
+        assert_eq!(parse_min_tls_version(Some("bogus")), tls::Version::TLS_1_2);
+    }
+}